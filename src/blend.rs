@@ -0,0 +1,200 @@
+//! Pixel-averaging redaction effects: mosaic (pixelate) and box blur.
+//! Both sample from an unmodified source buffer so they stay idempotent
+//! across redraws, which rebuild `modified_screenshot` from
+//! `original_screenshot` every frame.
+
+pub fn pixelate(
+    buffer: &mut [u8],
+    source: &[u8],
+    x0: usize,
+    y0: usize,
+    x1: usize,
+    y1: usize,
+    width: usize,
+    block_size: usize,
+) {
+    let (x0, x1) = (x0.min(x1), x0.max(x1));
+    let (y0, y1) = (y0.min(y1), y0.max(y1));
+
+    let mut by = y0;
+    while by < y1 {
+        let block_h = block_size.min(y1 - by);
+        let mut bx = x0;
+        while bx < x1 {
+            let block_w = block_size.min(x1 - bx);
+            let color = average_block(source, bx, by, block_w, block_h, width);
+            fill_block(buffer, bx, by, block_w, block_h, width, color);
+            bx += block_size;
+        }
+        by += block_size;
+    }
+}
+
+pub fn box_blur(
+    buffer: &mut [u8],
+    source: &[u8],
+    x0: usize,
+    y0: usize,
+    x1: usize,
+    y1: usize,
+    width: usize,
+    radius: usize,
+) {
+    let (x0, x1) = (x0.min(x1), x0.max(x1));
+    let (y0, y1) = (y0.min(y1), y0.max(y1));
+
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let color = average_window(source, x, y, radius, width, x0, y0, x1, y1);
+            set_pixel(buffer, x, y, width, color);
+        }
+    }
+}
+
+fn average_block(
+    source: &[u8],
+    x: usize,
+    y: usize,
+    block_w: usize,
+    block_h: usize,
+    width: usize,
+) -> (u8, u8, u8, u8) {
+    let mut sum = [0u64; 4];
+    let mut count = 0u64;
+
+    for row in 0..block_h {
+        for col in 0..block_w {
+            let idx = (y + row) * (width * 4) + (x + col) * 4;
+            for (channel, value) in sum.iter_mut().zip(&source[idx..idx + 4]) {
+                *channel += *value as u64;
+            }
+            count += 1;
+        }
+    }
+
+    average_color(sum, count)
+}
+
+fn average_window(
+    source: &[u8],
+    x: usize,
+    y: usize,
+    radius: usize,
+    width: usize,
+    min_x: usize,
+    min_y: usize,
+    max_x: usize,
+    max_y: usize,
+) -> (u8, u8, u8, u8) {
+    let x_start = x.saturating_sub(radius).max(min_x);
+    let x_end = (x + radius + 1).min(max_x);
+    let y_start = y.saturating_sub(radius).max(min_y);
+    let y_end = (y + radius + 1).min(max_y);
+
+    let mut sum = [0u64; 4];
+    let mut count = 0u64;
+
+    for wy in y_start..y_end {
+        for wx in x_start..x_end {
+            let idx = wy * (width * 4) + wx * 4;
+            for (channel, value) in sum.iter_mut().zip(&source[idx..idx + 4]) {
+                *channel += *value as u64;
+            }
+            count += 1;
+        }
+    }
+
+    average_color(sum, count)
+}
+
+fn average_color(sum: [u64; 4], count: u64) -> (u8, u8, u8, u8) {
+    let count = count.max(1);
+    (
+        (sum[0] / count) as u8,
+        (sum[1] / count) as u8,
+        (sum[2] / count) as u8,
+        (sum[3] / count) as u8,
+    )
+}
+
+fn fill_block(
+    buffer: &mut [u8],
+    x: usize,
+    y: usize,
+    block_w: usize,
+    block_h: usize,
+    width: usize,
+    color: (u8, u8, u8, u8),
+) {
+    for row in 0..block_h {
+        for col in 0..block_w {
+            set_pixel(buffer, x + col, y + row, width, color);
+        }
+    }
+}
+
+fn set_pixel(buffer: &mut [u8], x: usize, y: usize, width: usize, color: (u8, u8, u8, u8)) {
+    let idx = y * (width * 4) + x * 4;
+    buffer[idx] = color.0;
+    buffer[idx + 1] = color.1;
+    buffer[idx + 2] = color.2;
+    buffer[idx + 3] = color.3;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkerboard(width: usize, height: usize) -> Vec<u8> {
+        let mut buffer = vec![0u8; width * height * 4];
+        for y in 0..height {
+            for x in 0..width {
+                let color = if (x + y) % 2 == 0 {
+                    (255, 255, 255, 255)
+                } else {
+                    (0, 0, 0, 255)
+                };
+                set_pixel(&mut buffer, x, y, width, color);
+            }
+        }
+        buffer
+    }
+
+    #[test]
+    fn average_block_averages_a_uniform_region() {
+        let width = 4;
+        let mut source = vec![0u8; width * width * 4];
+        for y in 0..2 {
+            for x in 0..2 {
+                set_pixel(&mut source, x, y, width, (100, 150, 200, 255));
+            }
+        }
+
+        assert_eq!(average_block(&source, 0, 0, 2, 2, width), (100, 150, 200, 255));
+    }
+
+    #[test]
+    fn average_block_blends_a_checkerboard_to_mid_gray() {
+        let width = 4;
+        let source = checkerboard(width, width);
+
+        assert_eq!(average_block(&source, 0, 0, 2, 2, width), (127, 127, 127, 255));
+    }
+
+    #[test]
+    fn average_window_clamps_to_the_selection_boundary() {
+        let width = 4;
+        let source = checkerboard(width, width);
+
+        // A window centered on the top-left corner with a radius larger
+        // than the selection must not sample outside [min, max), the same
+        // bound `box_blur` passes in for the selected rectangle.
+        let color = average_window(&source, 0, 0, 5, width, 0, 0, 2, 2);
+        assert_eq!(color, (127, 127, 127, 255));
+    }
+
+    #[test]
+    fn average_color_never_divides_by_zero() {
+        assert_eq!(average_color([0, 0, 0, 0], 0), (0, 0, 0, 0));
+    }
+}
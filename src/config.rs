@@ -0,0 +1,273 @@
+//! Loads keybindings from an XDG config file so hotkeys can be rebound
+//! without recompiling, falling back to the built-in defaults when no
+//! config file is present.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use winit::event::{ModifiersState, VirtualKeyCode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Capture,
+    CaptureFocused,
+    SaveToFile,
+    Line,
+    RectBorder,
+    RectFilled,
+    Freehand,
+    Pixelate,
+    Blur,
+    ToggleFill,
+    Undo,
+    Redo,
+    Quit,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawConfig {
+    #[serde(default)]
+    keybindings: HashMap<String, String>,
+}
+
+pub struct Config {
+    keybindings: HashMap<(ModifiersState, VirtualKeyCode), Action>,
+}
+
+impl Config {
+    pub fn load() -> Self {
+        Self::load_from_file().unwrap_or_default()
+    }
+
+    fn load_from_file() -> Option<Self> {
+        let path = config_path()?;
+        let contents = fs::read_to_string(path).ok()?;
+        let raw: RawConfig = toml::from_str(&contents).ok()?;
+
+        let mut keybindings = default_keybindings();
+        for (action_name, accelerator) in raw.keybindings {
+            let Some(action) = action_from_name(&action_name) else {
+                continue;
+            };
+            let Some(binding) = parse_accelerator(&accelerator) else {
+                continue;
+            };
+            keybindings.insert(binding, action);
+        }
+
+        Some(Self { keybindings })
+    }
+
+    pub fn action_for(&self, modifiers: ModifiersState, keycode: VirtualKeyCode) -> Option<Action> {
+        self.keybindings.get(&(modifiers, keycode)).copied()
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            keybindings: default_keybindings(),
+        }
+    }
+}
+
+fn config_path() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(config_dir.join("birdy").join("config.toml"))
+}
+
+fn default_keybindings() -> HashMap<(ModifiersState, VirtualKeyCode), Action> {
+    use VirtualKeyCode::*;
+
+    let no_mods = ModifiersState::empty();
+    HashMap::from([
+        ((no_mods, Return), Action::Capture),
+        ((no_mods, F), Action::CaptureFocused),
+        ((no_mods, S), Action::SaveToFile),
+        ((no_mods, L), Action::Line),
+        ((no_mods, R), Action::RectBorder),
+        ((no_mods, P), Action::RectFilled),
+        ((no_mods, B), Action::Freehand),
+        ((no_mods, M), Action::Pixelate),
+        ((no_mods, G), Action::Blur),
+        ((no_mods, T), Action::ToggleFill),
+        ((no_mods, U), Action::Undo),
+        ((no_mods, Y), Action::Redo),
+        ((no_mods, Escape), Action::Quit),
+    ])
+}
+
+fn action_from_name(name: &str) -> Option<Action> {
+    Some(match name {
+        "capture" => Action::Capture,
+        "capture_focused" => Action::CaptureFocused,
+        "save_to_file" => Action::SaveToFile,
+        "line" => Action::Line,
+        "rect_border" => Action::RectBorder,
+        "rect_filled" => Action::RectFilled,
+        "freehand" => Action::Freehand,
+        "pixelate" => Action::Pixelate,
+        "blur" => Action::Blur,
+        "toggle_fill" => Action::ToggleFill,
+        "undo" => Action::Undo,
+        "redo" => Action::Redo,
+        "quit" => Action::Quit,
+        _ => return None,
+    })
+}
+
+fn parse_accelerator(accelerator: &str) -> Option<(ModifiersState, VirtualKeyCode)> {
+    let mut parts: Vec<&str> = accelerator.split('+').map(|part| part.trim()).collect();
+    let key_token = parts.pop()?;
+
+    let mut modifiers = ModifiersState::empty();
+    for part in parts {
+        match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => modifiers |= ModifiersState::CTRL,
+            "shift" => modifiers |= ModifiersState::SHIFT,
+            "alt" => modifiers |= ModifiersState::ALT,
+            "super" | "cmd" | "meta" => modifiers |= ModifiersState::LOGO,
+            _ => return None,
+        }
+    }
+
+    Some((modifiers, parse_keycode(key_token)?))
+}
+
+fn parse_keycode(name: &str) -> Option<VirtualKeyCode> {
+    use VirtualKeyCode::*;
+
+    if name.len() == 1 {
+        let c = name.chars().next().unwrap();
+        if let Some(keycode) = match c.to_ascii_uppercase() {
+            'A' => Some(A),
+            'B' => Some(B),
+            'C' => Some(C),
+            'D' => Some(D),
+            'E' => Some(E),
+            'F' => Some(F),
+            'G' => Some(G),
+            'H' => Some(H),
+            'I' => Some(I),
+            'J' => Some(J),
+            'K' => Some(K),
+            'L' => Some(L),
+            'M' => Some(M),
+            'N' => Some(N),
+            'O' => Some(O),
+            'P' => Some(P),
+            'Q' => Some(Q),
+            'R' => Some(R),
+            'S' => Some(S),
+            'T' => Some(T),
+            'U' => Some(U),
+            'V' => Some(V),
+            'W' => Some(W),
+            'X' => Some(X),
+            'Y' => Some(Y),
+            'Z' => Some(Z),
+            _ => None,
+        } {
+            return Some(keycode);
+        }
+
+        if let Some(digit) = c.to_digit(10) {
+            return Some(match digit {
+                0 => Key0,
+                1 => Key1,
+                2 => Key2,
+                3 => Key3,
+                4 => Key4,
+                5 => Key5,
+                6 => Key6,
+                7 => Key7,
+                8 => Key8,
+                _ => Key9,
+            });
+        }
+    }
+
+    Some(match name.to_ascii_lowercase().as_str() {
+        "enter" | "return" => Return,
+        "esc" | "escape" => Escape,
+        "space" => Space,
+        "tab" => Tab,
+        "back" | "backspace" => Back,
+        "f1" => F1,
+        "f2" => F2,
+        "f3" => F3,
+        "f4" => F4,
+        "f5" => F5,
+        "f6" => F6,
+        "f7" => F7,
+        "f8" => F8,
+        "f9" => F9,
+        "f10" => F10,
+        "f11" => F11,
+        "f12" => F12,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_keycode_letter_is_case_insensitive() {
+        assert_eq!(parse_keycode("l"), Some(VirtualKeyCode::L));
+        assert_eq!(parse_keycode("L"), Some(VirtualKeyCode::L));
+    }
+
+    #[test]
+    fn parse_keycode_digit() {
+        assert_eq!(parse_keycode("1"), Some(VirtualKeyCode::Key1));
+        assert_eq!(parse_keycode("0"), Some(VirtualKeyCode::Key0));
+    }
+
+    #[test]
+    fn parse_keycode_named_key() {
+        assert_eq!(parse_keycode("Escape"), Some(VirtualKeyCode::Escape));
+        assert_eq!(parse_keycode("f12"), Some(VirtualKeyCode::F12));
+    }
+
+    #[test]
+    fn parse_keycode_unknown_returns_none() {
+        assert_eq!(parse_keycode("nonsense"), None);
+    }
+
+    #[test]
+    fn parse_accelerator_single_key_has_no_modifiers() {
+        assert_eq!(
+            parse_accelerator("L"),
+            Some((ModifiersState::empty(), VirtualKeyCode::L))
+        );
+    }
+
+    #[test]
+    fn parse_accelerator_combines_modifiers_case_insensitively() {
+        assert_eq!(
+            parse_accelerator("Ctrl+Shift+p"),
+            Some((ModifiersState::CTRL | ModifiersState::SHIFT, VirtualKeyCode::P))
+        );
+    }
+
+    #[test]
+    fn parse_accelerator_trims_whitespace_around_parts() {
+        assert_eq!(
+            parse_accelerator(" Ctrl + L "),
+            Some((ModifiersState::CTRL, VirtualKeyCode::L))
+        );
+    }
+
+    #[test]
+    fn parse_accelerator_unknown_modifier_returns_none() {
+        assert_eq!(parse_accelerator("Hyper+L"), None);
+    }
+}
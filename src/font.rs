@@ -0,0 +1,96 @@
+//! Minimal 3x5 bitmap font, used to render the minibuffer command line
+//! directly into the screenshot buffer without pulling in a text-shaping
+//! dependency.
+
+const GLYPH_WIDTH: usize = 3;
+const GLYPH_HEIGHT: usize = 5;
+const GLYPH_GAP: usize = 1;
+
+pub fn draw_text(
+    buffer: &mut [u8],
+    x: usize,
+    y: usize,
+    text: &str,
+    buffer_width: usize,
+    color: (u8, u8, u8, u8),
+) {
+    for (i, c) in text.chars().enumerate() {
+        let glyph_x = x + i * (GLYPH_WIDTH + GLYPH_GAP);
+        draw_glyph(buffer, glyph_x, y, glyph(c), buffer_width, color);
+    }
+}
+
+fn draw_glyph(
+    buffer: &mut [u8],
+    x: usize,
+    y: usize,
+    rows: [u8; GLYPH_HEIGHT],
+    buffer_width: usize,
+    color: (u8, u8, u8, u8),
+) {
+    for (row, bits) in rows.iter().enumerate() {
+        for col in 0..GLYPH_WIDTH {
+            if bits & (1 << (GLYPH_WIDTH - 1 - col)) != 0 {
+                set_pixel(buffer, x + col, y + row, buffer_width, color);
+            }
+        }
+    }
+}
+
+fn set_pixel(buffer: &mut [u8], x: usize, y: usize, buffer_width: usize, color: (u8, u8, u8, u8)) {
+    let idx = y * (buffer_width * 4) + x * 4;
+    if idx + 3 < buffer.len() {
+        buffer[idx] = color.0;
+        buffer[idx + 1] = color.1;
+        buffer[idx + 2] = color.2;
+        buffer[idx + 3] = color.3;
+    }
+}
+
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT] {
+    match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'a' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'b' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'c' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'd' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'e' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'f' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'g' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'h' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'i' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'j' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'k' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'l' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'm' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'n' => [0b101, 0b111, 0b111, 0b111, 0b101],
+        'o' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'p' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'q' => [0b010, 0b101, 0b101, 0b111, 0b011],
+        'r' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        's' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        't' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'u' => [0b101, 0b101, 0b101, 0b101, 0b011],
+        'v' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'w' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'x' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'y' => [0b101, 0b101, 0b011, 0b001, 0b110],
+        'z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '=' => [0b000, 0b111, 0b000, 0b111, 0b000],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '-' | '_' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        ' ' => [0b000, 0b000, 0b000, 0b000, 0b000],
+        _ => [0b111, 0b111, 0b111, 0b111, 0b111],
+    }
+}
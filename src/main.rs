@@ -2,33 +2,46 @@
 
 use std::io::Read;
 use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::{env, process};
 
 #[cfg(target_os = "linux")]
 use arboard::SetExtLinux;
 use arboard::{Clipboard, ImageData};
 use error_iter::ErrorIter as _;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::{ColorType, ImageEncoder};
 use line::draw_line;
-use log::error;
+use log::{error, warn};
 use pixels::{Error, Pixels, SurfaceTexture};
 use rectangle::draw_rect_borders;
 use rectangle::draw_rect_filled;
 use screenshots::Screen;
 use serde::{Deserialize, Serialize};
-use winit::dpi::PhysicalPosition;
-use winit::event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{
+    ElementState, Event, KeyboardInput, ModifiersState, VirtualKeyCode, WindowEvent,
+};
 use winit::event_loop::{ControlFlow, EventLoop};
-use winit::window::Fullscreen;
-use winit::window::WindowBuilder;
+#[cfg(target_os = "linux")]
+use winit::platform::unix::EventLoopWindowTargetExtUnix;
+use winit::window::{Fullscreen, WindowBuilder};
 use winit_input_helper::WinitInputHelper;
 
 const BORDER_COLOR: (u8, u8, u8, u8) = (255, 0, 255, 255);
 
 mod blend;
 mod circle;
+mod config;
+mod font;
 mod line;
+mod minibuffer;
 mod rectangle;
 
+use config::{Action, Config};
+use minibuffer::{parse_hex_color, Command, MiniBuffer};
+
 const DAEMONIZE_ARG: &str = "__internal_daemonize";
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -59,8 +72,32 @@ Hotkeys:
   l - draw a line
   r - draw a rectangular border
   p - draw a filled rectangle
+  b - draw freehand, brush-style
+  m - redact a rectangular area by pixelating it
+  g - redact a rectangular area by blurring it
   t - toggle latest drawn shape between filled/not filled states
 
+  u - undo the last annotation
+  y - redo the last undone annotation
+
+  : - open the command minibuffer (:clear, :undo, :w <path>, :set color = <hex>)
+
+  1-9 - pick the active annotation color from the palette strip
+
+  s - save selected area to the file given by --output/-o and exit
+
+Flags:
+  --output, -o <path>  file the "s" hotkey writes the capture to (PNG,
+                        or JPEG by extension); pass "-" to write to
+                        stdout
+
+Keybindings can be customized via $XDG_CONFIG_HOME/birdy/config.toml (or
+~/.config/birdy/config.toml), e.g.:
+
+  [keybindings]
+  capture = "Enter"
+  line = "Ctrl+L"
+
   Esc - exit
 "#
         );
@@ -92,16 +129,41 @@ Hotkeys:
         return Ok(());
     }
 
+    let output_path = parse_output_path(env::args());
+    let config = Config::load();
+    let mut modifiers = ModifiersState::empty();
+
     env_logger::init();
     let event_loop = EventLoop::new();
     let mut input = WinitInputHelper::new();
+
+    let (origin_x, origin_y, desktop_width, desktop_height) = virtual_desktop_bounds();
+
+    #[cfg(target_os = "linux")]
+    let is_wayland = event_loop.is_wayland();
+    #[cfg(not(target_os = "linux"))]
+    let is_wayland = false;
+
     let window = {
-        WindowBuilder::new()
+        let builder = WindowBuilder::new()
             .with_title("Hello Pixels")
-            .with_fullscreen(Some(Fullscreen::Borderless(None)))
-            .with_maximized(true)
-            .build(&event_loop)
-            .unwrap()
+            .with_decorations(false);
+
+        // Wayland clients can't place or size their own toplevel surface,
+        // so `with_position`/`with_inner_size` are silently ignored there;
+        // fall back to a borderless fullscreen window on the
+        // compositor-chosen output instead of risking one placed
+        // arbitrarily and undersized.
+        let builder = if is_wayland {
+            warn!("multi-monitor capture is unsupported under Wayland; capturing the first screen only");
+            builder.with_fullscreen(Some(Fullscreen::Borderless(None)))
+        } else {
+            builder
+                .with_position(PhysicalPosition::new(origin_x, origin_y))
+                .with_inner_size(PhysicalSize::new(desktop_width as u32, desktop_height as u32))
+        };
+
+        builder.build(&event_loop).unwrap()
     };
 
     let mut pixels = {
@@ -113,6 +175,8 @@ Hotkeys:
     let mut screenshot = Screenshot::new(
         window.inner_size().width as usize,
         window.inner_size().height as usize,
+        window.scale_factor(),
+        is_wayland,
     );
 
     event_loop.run(move |event, _, control_flow| {
@@ -164,39 +228,109 @@ Hotkeys:
                     },
                 ..
             } => {
-                if let Some(VirtualKeyCode::Return) = virtual_keycode {
-                    screenshot.save_image_to_clipboard(screenshot.get_clipped_image());
-                    *control_flow = ControlFlow::Exit;
-                    return;
-                }
-                if let Some(VirtualKeyCode::F) = virtual_keycode {
-                    screenshot.save_image_to_clipboard(screenshot.get_focused_image());
-                    *control_flow = ControlFlow::Exit;
+                if screenshot.minibuffer.active {
+                    if let Some(VirtualKeyCode::Return) = virtual_keycode {
+                        let command = screenshot.minibuffer.take_command();
+                        screenshot.run_command(command);
+                    } else if let Some(VirtualKeyCode::Escape) = virtual_keycode {
+                        screenshot.minibuffer.close();
+                    } else if let Some(VirtualKeyCode::Back) = virtual_keycode {
+                        screenshot.minibuffer.backspace();
+                    }
+
+                    window.request_redraw();
                     return;
                 }
 
-                if let Some(VirtualKeyCode::L) = virtual_keycode {
-                    screenshot.draw_mode = Some(DrawMode::Line);
-                }
-                if let Some(VirtualKeyCode::R) = virtual_keycode {
-                    screenshot.draw_mode = Some(DrawMode::RectBorder);
-                }
-                if let Some(VirtualKeyCode::P) = virtual_keycode {
-                    screenshot.draw_mode = Some(DrawMode::RectFilled);
+                if let Some(action) = virtual_keycode.and_then(|keycode| config.action_for(modifiers, keycode))
+                {
+                    match action {
+                        Action::Capture => {
+                            screenshot.save_image_to_clipboard(screenshot.get_clipped_image());
+                            *control_flow = ControlFlow::Exit;
+                            return;
+                        }
+                        Action::CaptureFocused => {
+                            screenshot.save_image_to_clipboard(screenshot.get_focused_image());
+                            *control_flow = ControlFlow::Exit;
+                            return;
+                        }
+                        Action::SaveToFile => match &output_path {
+                            Some(path) => {
+                                screenshot
+                                    .save_image_to_file(screenshot.get_clipped_image(), path);
+                                *control_flow = ControlFlow::Exit;
+                                return;
+                            }
+                            None => error!("no output path configured; pass --output <path>"),
+                        },
+                        Action::Line => screenshot.draw_mode = Some(DrawMode::Line),
+                        Action::RectBorder => screenshot.draw_mode = Some(DrawMode::RectBorder),
+                        Action::RectFilled => screenshot.draw_mode = Some(DrawMode::RectFilled),
+                        Action::Freehand => screenshot.draw_mode = Some(DrawMode::Freehand),
+                        Action::Pixelate => screenshot.draw_mode = Some(DrawMode::Pixelate),
+                        Action::Blur => screenshot.draw_mode = Some(DrawMode::Blur),
+                        Action::ToggleFill => screenshot.toggle_filling_latest(),
+                        Action::Undo => screenshot.undo(),
+                        Action::Redo => screenshot.redo(),
+                        Action::Quit => {
+                            *control_flow = ControlFlow::Exit;
+                            return;
+                        }
+                    }
+                } else if let Some(index) = virtual_keycode.and_then(palette_index) {
+                    // Only falls back to palette selection when the user
+                    // hasn't rebound this key to an action themselves.
+                    screenshot.select_color(index);
                 }
-                if let Some(VirtualKeyCode::T) = virtual_keycode {
-                    screenshot.toggle_filling_latest();
+
+                window.request_redraw();
+            }
+
+            Event::WindowEvent {
+                event: WindowEvent::ModifiersChanged(new_modifiers),
+                ..
+            } => {
+                modifiers = new_modifiers;
+            }
+
+            Event::WindowEvent {
+                event: WindowEvent::ReceivedCharacter(c),
+                ..
+            } => {
+                if screenshot.minibuffer.active {
+                    if c != '\u{8}' && c != '\r' && c != '\n' {
+                        screenshot.minibuffer.push_char(c);
+                    }
+                } else if c == ':' {
+                    screenshot.minibuffer.open();
                 }
 
                 window.request_redraw();
             }
 
+            Event::WindowEvent {
+                event:
+                    WindowEvent::ScaleFactorChanged {
+                        scale_factor,
+                        new_inner_size,
+                    },
+                ..
+            } => {
+                screenshot.set_scale_factor(
+                    scale_factor,
+                    new_inner_size.width as usize,
+                    new_inner_size.height as usize,
+                );
+                window.request_redraw();
+            }
+
             _ => {}
         }
 
         // Handle input events
         if input.update(&event) {
-            if input.key_pressed(VirtualKeyCode::Escape) || input.close_requested() {
+            if input.close_requested() {
                 *control_flow = ControlFlow::Exit;
                 return;
             }
@@ -228,6 +362,130 @@ fn log_error<E: std::error::Error + 'static>(method_name: &str, err: E) {
     }
 }
 
+fn parse_output_path(args: env::Args) -> Option<PathBuf> {
+    let mut args = args.skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--output" || arg == "-o" {
+            return args.next().map(PathBuf::from);
+        }
+    }
+    None
+}
+
+/// Bounding rectangle of the virtual desktop formed by every connected
+/// screen, in global display coordinates.
+fn virtual_desktop_bounds() -> (i32, i32, usize, usize) {
+    let screens = Screen::all().unwrap();
+
+    let min_x = screens.iter().map(|screen| screen.display_info.x).min().unwrap_or(0);
+    let min_y = screens.iter().map(|screen| screen.display_info.y).min().unwrap_or(0);
+    let max_x = screens
+        .iter()
+        .map(|screen| screen.display_info.x + screen.display_info.width as i32)
+        .max()
+        .unwrap_or(0);
+    let max_y = screens
+        .iter()
+        .map(|screen| screen.display_info.y + screen.display_info.height as i32)
+        .max()
+        .unwrap_or(0);
+
+    (min_x, min_y, (max_x - min_x).max(0) as usize, (max_y - min_y).max(0) as usize)
+}
+
+/// Captures only the first available screen. Used instead of
+/// `capture_virtual_desktop` when the window can only cover a single
+/// output (Wayland), keeping the capture buffer's size in sync with the
+/// window actually shown.
+fn capture_primary_screen() -> (usize, usize, Vec<u8>) {
+    let screens = Screen::all().unwrap();
+    let Some(screen) = screens.first() else {
+        panic!("can't find an available screen for a screenshot");
+    };
+
+    let image = screen.capture().unwrap();
+    (image.width() as usize, image.height() as usize, image.to_vec())
+}
+
+/// Captures every connected screen and composites them into a single
+/// buffer sized to the virtual desktop, each screen placed at its
+/// offset from the top-left-most display.
+fn capture_virtual_desktop() -> (usize, usize, Vec<u8>) {
+    let screens = Screen::all().unwrap();
+    if screens.is_empty() {
+        panic!("can't find an available screen for a screenshot");
+    }
+
+    let (origin_x, origin_y, width, height) = virtual_desktop_bounds();
+    let mut buffer = vec![0u8; width * height * 4];
+
+    for screen in &screens {
+        let image = match screen.capture() {
+            Ok(image) => image,
+            Err(err) => {
+                log_error("screen.capture", err);
+                continue;
+            }
+        };
+
+        let offset_x = (screen.display_info.x - origin_x) as usize;
+        let offset_y = (screen.display_info.y - origin_y) as usize;
+        let screen_width = image.width() as usize;
+        let screen_height = image.height() as usize;
+
+        // `display_info` and the actual capture can disagree (e.g. mixed
+        // DPI setups); skip a screen that would write outside the
+        // composited buffer instead of panicking on a short copy.
+        if offset_x + screen_width > width || offset_y + screen_height > height {
+            error!(
+                "screen capture size {screen_width}x{screen_height} at ({offset_x}, {offset_y}) doesn't fit the {width}x{height} virtual desktop; skipping"
+            );
+            continue;
+        }
+
+        let bytes = image.to_vec();
+        let row_len = screen_width * 4;
+
+        for y in 0..screen_height {
+            let dst_start = (offset_y + y) * (width * 4) + offset_x * 4;
+            let src_start = y * row_len;
+            buffer[dst_start..dst_start + row_len].copy_from_slice(&bytes[src_start..src_start + row_len]);
+        }
+    }
+
+    (width, height, buffer)
+}
+
+fn encode_image(image: &Image, path: &Path, writer: impl Write) -> image::ImageResult<()> {
+    let is_jpeg = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.eq_ignore_ascii_case("jpg") || ext.eq_ignore_ascii_case("jpeg"))
+        .unwrap_or(false);
+
+    if is_jpeg {
+        // JPEG has no alpha channel; the encoder doesn't accept Rgba8, so
+        // drop the alpha byte from each pixel before handing it over.
+        JpegEncoder::new(writer).write_image(
+            &rgba_to_rgb(&image.bytes),
+            image.width as u32,
+            image.height as u32,
+            ColorType::Rgb8,
+        )
+    } else {
+        PngEncoder::new(writer).write_image(
+            &image.bytes,
+            image.width as u32,
+            image.height as u32,
+            ColorType::Rgba8,
+        )
+    }
+}
+
+fn rgba_to_rgb(bytes: &[u8]) -> Vec<u8> {
+    bytes.chunks_exact(4).flat_map(|pixel| &pixel[..3]).copied().collect()
+}
+
 struct Screenshot {
     original_screenshot: Vec<u8>,
     modified_screenshot: Vec<u8>,
@@ -245,18 +503,33 @@ struct Screenshot {
     draw_mode: Option<DrawMode>,
     drawing_item: Option<DrawnItem>,
     drawn_items: Vec<DrawnItem>,
+    redo_stack: Vec<DrawnItem>,
+    current_color: (u8, u8, u8, u8),
+
+    minibuffer: MiniBuffer,
+
+    // `width`/`height` are the capture buffer's native resolution; on a
+    // fractionally-scaled display these differ from the window's own
+    // physical size, so cursor positions have to be rescaled into
+    // capture space before they can index into the buffers below.
+    viewport_width: usize,
+    viewport_height: usize,
+    scale_factor: f64,
+
+    // Set when the windowing backend can't place/size a window across the
+    // full virtual desktop (Wayland), so the window only covers one
+    // output; the capture buffer is restricted to match.
+    single_screen: bool,
 
     mouse_coordinates: Option<PhysicalPosition<f64>>,
 }
 
 impl Screenshot {
-    fn new(width: usize, height: usize) -> Self {
-        let screens = Screen::all().unwrap();
-        let original_screenshot = if let Some(screen) = screens.get(0) {
-            let image = screen.capture().unwrap();
-            image.to_vec()
+    fn new(viewport_width: usize, viewport_height: usize, scale_factor: f64, single_screen: bool) -> Self {
+        let (width, height, original_screenshot) = if single_screen {
+            capture_primary_screen()
         } else {
-            panic!("can't find an available screen for a screenshot");
+            capture_virtual_desktop()
         };
 
         Self {
@@ -272,6 +545,15 @@ impl Screenshot {
             draw_mode: None,
             drawing_item: None,
             drawn_items: vec![],
+            redo_stack: vec![],
+            current_color: PALETTE[0],
+
+            minibuffer: MiniBuffer::default(),
+
+            viewport_width,
+            viewport_height,
+            scale_factor,
+            single_screen,
 
             p0: (0, 0),
             p1: (width, height),
@@ -282,7 +564,28 @@ impl Screenshot {
     }
 
     pub fn resize_viewport(&mut self, width: usize, height: usize) {
-        *self = Self::new(width, height);
+        let scale_factor = self.scale_factor;
+        let single_screen = self.single_screen;
+        *self = Self::new(width, height, scale_factor, single_screen);
+    }
+
+    pub fn set_scale_factor(&mut self, scale_factor: f64, viewport_width: usize, viewport_height: usize) {
+        self.scale_factor = scale_factor;
+        self.viewport_width = viewport_width;
+        self.viewport_height = viewport_height;
+    }
+
+    /// Maps a cursor position from window/viewport space into capture
+    /// buffer space, accounting for the ratio between the two (which
+    /// diverges from 1:1 under fractional display scaling).
+    fn to_capture_space(&self, position: PhysicalPosition<f64>) -> PhysicalPosition<f64> {
+        let scale_x = self.width as f64 / self.viewport_width.max(1) as f64;
+        let scale_y = self.height as f64 / self.viewport_height.max(1) as f64;
+
+        PhysicalPosition {
+            x: position.x * scale_x,
+            y: position.y * scale_y,
+        }
     }
 
     fn get_focused_image(&self) -> Image {
@@ -342,6 +645,21 @@ impl Screenshot {
         }
     }
 
+    pub fn save_image_to_file(&self, image: Image, path: &Path) {
+        let result = if path.as_os_str() == "-" {
+            encode_image(&image, path, std::io::stdout().lock())
+        } else {
+            match std::fs::File::create(path) {
+                Ok(file) => encode_image(&image, path, file),
+                Err(err) => Err(err.into()),
+            }
+        };
+
+        if let Err(err) = result {
+            log_error("save_image_to_file", err);
+        }
+    }
+
     fn draw(&mut self, pixels: &mut [u8]) {
         self.modified_screenshot = self.original_screenshot.clone();
         self.draw_boundaries();
@@ -351,18 +669,33 @@ impl Screenshot {
             self.draw_draw_item(&draw_item);
         }
 
-        if let Some(drawing_item) = self.drawing_item {
+        if let Some(drawing_item) = self.drawing_item.clone() {
             self.draw_draw_item(&drawing_item);
         }
 
+        if self.minibuffer.active {
+            let line = format!(":{}", self.minibuffer.input);
+            font::draw_text(
+                &mut self.modified_screenshot,
+                10,
+                self.height.saturating_sub(20),
+                &line,
+                self.width,
+                self.current_color,
+            );
+        }
+
         if pixels.len() == self.modified_screenshot.len() {
             pixels.copy_from_slice(&self.modified_screenshot);
+            // Drawn only into the render target, never into
+            // `modified_screenshot`, so captures stay free of the palette UI.
+            self.draw_palette(pixels);
         }
     }
 
     fn draw_draw_item(&mut self, draw_item: &DrawnItem) {
         match draw_item {
-            DrawnItem::Line((x0, y0), (x1, y1)) => {
+            DrawnItem::Line((x0, y0), (x1, y1), color) => {
                 draw_line(
                     &mut self.modified_screenshot,
                     *x0,
@@ -370,10 +703,10 @@ impl Screenshot {
                     *x1,
                     *y1,
                     self.width,
-                    BORDER_COLOR,
+                    *color,
                 );
             }
-            DrawnItem::RectBorder((x0, y0), (x1, y1)) => {
+            DrawnItem::RectBorder((x0, y0), (x1, y1), color) => {
                 draw_rect_borders(
                     &mut self.modified_screenshot,
                     *x0,
@@ -381,10 +714,10 @@ impl Screenshot {
                     *x1,
                     *y1,
                     self.width,
-                    BORDER_COLOR,
+                    *color,
                 );
             }
-            DrawnItem::RectFilled((x0, y0), (x1, y1)) => {
+            DrawnItem::RectFilled((x0, y0), (x1, y1), color) => {
                 draw_rect_filled(
                     &mut self.modified_screenshot,
                     *x0,
@@ -392,7 +725,46 @@ impl Screenshot {
                     *x1,
                     *y1,
                     self.width,
-                    BORDER_COLOR,
+                    *color,
+                );
+            }
+            DrawnItem::Freehand(points, color) => {
+                for pair in points.windows(2) {
+                    let (x0, y0) = pair[0];
+                    let (x1, y1) = pair[1];
+                    draw_line(
+                        &mut self.modified_screenshot,
+                        x0,
+                        y0,
+                        x1,
+                        y1,
+                        self.width,
+                        *color,
+                    );
+                }
+            }
+            DrawnItem::Pixelate((x0, y0), (x1, y1)) => {
+                blend::pixelate(
+                    &mut self.modified_screenshot,
+                    &self.original_screenshot,
+                    *x0,
+                    *y0,
+                    *x1,
+                    *y1,
+                    self.width,
+                    PIXELATE_BLOCK_SIZE,
+                );
+            }
+            DrawnItem::Blur((x0, y0), (x1, y1)) => {
+                blend::box_blur(
+                    &mut self.modified_screenshot,
+                    &self.original_screenshot,
+                    *x0,
+                    *y0,
+                    *x1,
+                    *y1,
+                    self.width,
+                    BLUR_RADIUS,
                 );
             }
         }
@@ -420,6 +792,26 @@ impl Screenshot {
         }
     }
 
+    fn draw_palette(&self, buffer: &mut [u8]) {
+        for (index, color) in PALETTE.iter().enumerate() {
+            let x0 = PALETTE_SWATCH_GAP + index * (PALETTE_SWATCH_SIZE + PALETTE_SWATCH_GAP);
+            let y0 = PALETTE_SWATCH_GAP;
+            let x1 = x0 + PALETTE_SWATCH_SIZE;
+            let y1 = y0 + PALETTE_SWATCH_SIZE;
+
+            draw_rect_filled(buffer, x0, y0, x1, y1, self.width, *color);
+            if *color == self.current_color {
+                draw_rect_borders(buffer, x0, y0, x1, y1, self.width, BORDER_COLOR);
+            }
+        }
+    }
+
+    pub fn select_color(&mut self, index: usize) {
+        if let Some(color) = PALETTE.get(index) {
+            self.current_color = *color;
+        }
+    }
+
     pub fn toggle_filling_latest(&mut self) {
         if let Some(item) = self.drawn_items.pop() {
             let filled_item = self.toggle_item_filling(&item);
@@ -429,14 +821,47 @@ impl Screenshot {
 
     pub fn toggle_item_filling(&mut self, draw_item: &DrawnItem) -> DrawnItem {
         match draw_item {
-            DrawnItem::Line(..) => *draw_item,
-            DrawnItem::RectBorder(p0, p1) => DrawnItem::RectFilled(*p0, *p1),
-            DrawnItem::RectFilled(p0, p1) => DrawnItem::RectBorder(*p0, *p1),
+            DrawnItem::Line(..) => draw_item.clone(),
+            DrawnItem::RectBorder(p0, p1, color) => DrawnItem::RectFilled(*p0, *p1, *color),
+            DrawnItem::RectFilled(p0, p1, color) => DrawnItem::RectBorder(*p0, *p1, *color),
+            DrawnItem::Freehand(..) => draw_item.clone(),
+            DrawnItem::Pixelate(..) => draw_item.clone(),
+            DrawnItem::Blur(..) => draw_item.clone(),
+        }
+    }
+
+    pub fn undo(&mut self) {
+        if let Some(item) = self.drawn_items.pop() {
+            self.redo_stack.push(item);
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(item) = self.redo_stack.pop() {
+            self.drawn_items.push(item);
+        }
+    }
+
+    pub fn run_command(&mut self, command: Command) {
+        match command {
+            Command::Clear => {
+                self.drawn_items.clear();
+                self.redo_stack.clear();
+            }
+            Command::Undo => self.undo(),
+            Command::Write(path) => {
+                self.save_image_to_file(self.get_clipped_image(), Path::new(&path));
+            }
+            Command::SetColor(hex) => match parse_hex_color(&hex) {
+                Some(color) => self.current_color = color,
+                None => error!("invalid color: {hex}"),
+            },
+            Command::Unknown(raw) => error!("unknown command: {raw}"),
         }
     }
 
     pub fn on_mouse_move(&mut self, coordinates: PhysicalPosition<f64>) {
-        self.mouse_coordinates = Some(coordinates);
+        self.mouse_coordinates = Some(self.to_capture_space(coordinates));
 
         if self.is_resizing && self.top_border_resized {
             self.p0.1 = self.mouse_coordinates.unwrap().y as usize;
@@ -449,21 +874,42 @@ impl Screenshot {
         } else {
             match self.draw_mode {
                 Some(DrawMode::Line) => {
-                    if let (Some(DrawnItem::Line(_, p1)), Some(PhysicalPosition { x, y })) =
+                    if let (Some(DrawnItem::Line(_, p1, _)), Some(PhysicalPosition { x, y })) =
                         (&mut self.drawing_item, self.mouse_coordinates)
                     {
                         *p1 = (x as usize, y as usize);
                     }
                 }
                 Some(DrawMode::RectBorder) => {
-                    if let (Some(DrawnItem::RectBorder(_, p1)), Some(PhysicalPosition { x, y })) =
+                    if let (Some(DrawnItem::RectBorder(_, p1, _)), Some(PhysicalPosition { x, y })) =
                         (&mut self.drawing_item, self.mouse_coordinates)
                     {
                         *p1 = (x as usize, y as usize);
                     }
                 }
                 Some(DrawMode::RectFilled) => {
-                    if let (Some(DrawnItem::RectFilled(_, p1)), Some(PhysicalPosition { x, y })) =
+                    if let (Some(DrawnItem::RectFilled(_, p1, _)), Some(PhysicalPosition { x, y })) =
+                        (&mut self.drawing_item, self.mouse_coordinates)
+                    {
+                        *p1 = (x as usize, y as usize);
+                    }
+                }
+                Some(DrawMode::Freehand) => {
+                    if let (Some(DrawnItem::Freehand(points, _)), Some(PhysicalPosition { x, y })) =
+                        (&mut self.drawing_item, self.mouse_coordinates)
+                    {
+                        points.push((x as usize, y as usize));
+                    }
+                }
+                Some(DrawMode::Pixelate) => {
+                    if let (Some(DrawnItem::Pixelate(_, p1)), Some(PhysicalPosition { x, y })) =
+                        (&mut self.drawing_item, self.mouse_coordinates)
+                    {
+                        *p1 = (x as usize, y as usize);
+                    }
+                }
+                Some(DrawMode::Blur) => {
+                    if let (Some(DrawnItem::Blur(_, p1)), Some(PhysicalPosition { x, y })) =
                         (&mut self.drawing_item, self.mouse_coordinates)
                     {
                         *p1 = (x as usize, y as usize);
@@ -516,13 +962,26 @@ impl Screenshot {
             } else {
                 match self.draw_mode {
                     Some(DrawMode::Line) => {
-                        self.drawing_item = Some(DrawnItem::Line((x, y), (x, y)));
+                        self.drawing_item =
+                            Some(DrawnItem::Line((x, y), (x, y), self.current_color));
                     }
                     Some(DrawMode::RectBorder) => {
-                        self.drawing_item = Some(DrawnItem::RectBorder((x, y), (x, y)));
+                        self.drawing_item =
+                            Some(DrawnItem::RectBorder((x, y), (x, y), self.current_color));
                     }
                     Some(DrawMode::RectFilled) => {
-                        self.drawing_item = Some(DrawnItem::RectFilled((x, y), (x, y)));
+                        self.drawing_item =
+                            Some(DrawnItem::RectFilled((x, y), (x, y), self.current_color));
+                    }
+                    Some(DrawMode::Freehand) => {
+                        self.drawing_item =
+                            Some(DrawnItem::Freehand(vec![(x, y)], self.current_color));
+                    }
+                    Some(DrawMode::Pixelate) => {
+                        self.drawing_item = Some(DrawnItem::Pixelate((x, y), (x, y)));
+                    }
+                    Some(DrawMode::Blur) => {
+                        self.drawing_item = Some(DrawnItem::Blur((x, y), (x, y)));
                     }
                     None => {}
                 }
@@ -539,29 +998,55 @@ impl Screenshot {
 
         match self.draw_mode {
             Some(DrawMode::Line) => {
-                if let (Some(DrawnItem::Line(p0, _)), Some(PhysicalPosition { x, y })) =
-                    (self.drawing_item, self.mouse_coordinates)
+                if let (Some(DrawnItem::Line(p0, _, color)), Some(PhysicalPosition { x, y })) =
+                    (self.drawing_item.clone(), self.mouse_coordinates)
                 {
                     self.drawn_items
-                        .push(DrawnItem::Line(p0, (x as usize, y as usize)));
+                        .push(DrawnItem::Line(p0, (x as usize, y as usize), color));
                     self.drawing_item = None;
                 }
             }
             Some(DrawMode::RectBorder) => {
-                if let (Some(DrawnItem::RectBorder(p0, _)), Some(PhysicalPosition { x, y })) =
-                    (self.drawing_item, self.mouse_coordinates)
+                if let (Some(DrawnItem::RectBorder(p0, _, color)), Some(PhysicalPosition { x, y })) =
+                    (self.drawing_item.clone(), self.mouse_coordinates)
                 {
                     self.drawn_items
-                        .push(DrawnItem::RectBorder(p0, (x as usize, y as usize)));
+                        .push(DrawnItem::RectBorder(p0, (x as usize, y as usize), color));
                     self.drawing_item = None;
                 }
             }
             Some(DrawMode::RectFilled) => {
-                if let (Some(DrawnItem::RectFilled(p0, _)), Some(PhysicalPosition { x, y })) =
-                    (self.drawing_item, self.mouse_coordinates)
+                if let (Some(DrawnItem::RectFilled(p0, _, color)), Some(PhysicalPosition { x, y })) =
+                    (self.drawing_item.clone(), self.mouse_coordinates)
                 {
                     self.drawn_items
-                        .push(DrawnItem::RectFilled(p0, (x as usize, y as usize)));
+                        .push(DrawnItem::RectFilled(p0, (x as usize, y as usize), color));
+                    self.drawing_item = None;
+                }
+            }
+            Some(DrawMode::Freehand) => {
+                if let Some(DrawnItem::Freehand(mut points, color)) = self.drawing_item.take() {
+                    if let Some(PhysicalPosition { x, y }) = self.mouse_coordinates {
+                        points.push((x as usize, y as usize));
+                    }
+                    self.drawn_items.push(DrawnItem::Freehand(points, color));
+                }
+            }
+            Some(DrawMode::Pixelate) => {
+                if let (Some(DrawnItem::Pixelate(p0, _)), Some(PhysicalPosition { x, y })) =
+                    (self.drawing_item.clone(), self.mouse_coordinates)
+                {
+                    self.drawn_items
+                        .push(DrawnItem::Pixelate(p0, (x as usize, y as usize)));
+                    self.drawing_item = None;
+                }
+            }
+            Some(DrawMode::Blur) => {
+                if let (Some(DrawnItem::Blur(p0, _)), Some(PhysicalPosition { x, y })) =
+                    (self.drawing_item.clone(), self.mouse_coordinates)
+                {
+                    self.drawn_items
+                        .push(DrawnItem::Blur(p0, (x as usize, y as usize)));
                     self.drawing_item = None;
                 }
             }
@@ -576,11 +1061,53 @@ enum DrawMode {
     Line,
     RectBorder,
     RectFilled,
+    Freehand,
+    Pixelate,
+    Blur,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 enum DrawnItem {
-    Line((usize, usize), (usize, usize)),
-    RectBorder((usize, usize), (usize, usize)),
-    RectFilled((usize, usize), (usize, usize)),
+    Line((usize, usize), (usize, usize), (u8, u8, u8, u8)),
+    RectBorder((usize, usize), (usize, usize), (u8, u8, u8, u8)),
+    RectFilled((usize, usize), (usize, usize), (u8, u8, u8, u8)),
+    Freehand(Vec<(usize, usize)>, (u8, u8, u8, u8)),
+    Pixelate((usize, usize), (usize, usize)),
+    Blur((usize, usize), (usize, usize)),
+}
+
+const PIXELATE_BLOCK_SIZE: usize = 12;
+const BLUR_RADIUS: usize = 4;
+
+/// Colors offered by the on-screen palette strip, selected with number
+/// keys 1-9; newly drawn shapes are stamped with whichever is active.
+const PALETTE: [(u8, u8, u8, u8); 9] = [
+    (255, 0, 0, 255),
+    (255, 127, 0, 255),
+    (255, 255, 0, 255),
+    (0, 200, 0, 255),
+    (0, 200, 200, 255),
+    (0, 0, 255, 255),
+    (127, 0, 255, 255),
+    (255, 0, 255, 255),
+    (255, 255, 255, 255),
+];
+const PALETTE_SWATCH_SIZE: usize = 16;
+const PALETTE_SWATCH_GAP: usize = 4;
+
+fn palette_index(keycode: VirtualKeyCode) -> Option<usize> {
+    use VirtualKeyCode::*;
+
+    Some(match keycode {
+        Key1 => 0,
+        Key2 => 1,
+        Key3 => 2,
+        Key4 => 3,
+        Key5 => 4,
+        Key6 => 5,
+        Key7 => 6,
+        Key8 => 7,
+        Key9 => 8,
+        _ => return None,
+    })
 }
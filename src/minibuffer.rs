@@ -0,0 +1,101 @@
+//! Colon-command minibuffer: captures a line of keyboard input and parses
+//! it into a `Command` for the main event loop to dispatch.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Clear,
+    Undo,
+    Write(String),
+    SetColor(String),
+    Unknown(String),
+}
+
+pub fn parse_command(line: &str) -> Command {
+    let line = line.trim();
+
+    if line == "clear" {
+        Command::Clear
+    } else if line == "undo" {
+        Command::Undo
+    } else if let Some(path) = line.strip_prefix("w ") {
+        Command::Write(path.trim().to_string())
+    } else if let Some(rest) = line.strip_prefix("set color") {
+        Command::SetColor(rest.trim().trim_start_matches('=').trim().to_string())
+    } else {
+        Command::Unknown(line.to_string())
+    }
+}
+
+pub fn parse_hex_color(hex: &str) -> Option<(u8, u8, u8, u8)> {
+    let hex = hex.trim().trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+    let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+    let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+    let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+    Some((r, g, b, 255))
+}
+
+#[derive(Default)]
+pub struct MiniBuffer {
+    pub active: bool,
+    pub input: String,
+}
+
+impl MiniBuffer {
+    pub fn open(&mut self) {
+        self.active = true;
+        self.input.clear();
+    }
+
+    pub fn push_char(&mut self, c: char) {
+        self.input.push(c);
+    }
+
+    pub fn backspace(&mut self) {
+        self.input.pop();
+    }
+
+    pub fn close(&mut self) {
+        self.active = false;
+        self.input.clear();
+    }
+
+    pub fn take_command(&mut self) -> Command {
+        let command = parse_command(&self.input);
+        self.close();
+        command
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_command_set_color_with_spaces_around_equals() {
+        assert_eq!(
+            parse_command("set color = ff0000"),
+            Command::SetColor("ff0000".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_command_set_color_without_spaces() {
+        assert_eq!(
+            parse_command("set color=ff0000"),
+            Command::SetColor("ff0000".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_hex_color_accepts_leading_hash() {
+        assert_eq!(parse_hex_color("#ff0000"), Some((255, 0, 0, 255)));
+    }
+
+    #[test]
+    fn parse_hex_color_rejects_wrong_length() {
+        assert_eq!(parse_hex_color("= ff0000"), None);
+    }
+}